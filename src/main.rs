@@ -1,25 +1,163 @@
+use std::collections::HashSet;
+
 use anyhow::{Context, Result};
-use winit::event::{Event, WindowEvent};
+use glam::Vec3;
+use winit::event::{ElementState, Event, MouseButton, WindowEvent};
 use winit::event_loop::{ControlFlow, EventLoop};
+use winit::keyboard::{KeyCode, PhysicalKey};
 use winit::window::{Window, WindowBuilder};
 
 const WIDTH: u32 = 960;
 const HEIGHT: u32 = 544;
+const MOVE_SPEED: f32 = 0.05;
+const LOOK_SPEED: f32 = 0.0025;
 
 mod render;
+mod scene;
+
+/// Tracks keyboard/mouse state for a fly-around camera and turns it into
+/// `render::Camera` values each frame.
+struct CameraController {
+  position: Vec3,
+  yaw: f32,
+  pitch: f32,
+  pressed_keys: HashSet<KeyCode>,
+  dragging: bool,
+  last_cursor: Option<(f64, f64)>,
+}
+
+impl CameraController {
+  fn new(position: Vec3) -> CameraController {
+    CameraController {
+      position,
+      yaw: -std::f32::consts::FRAC_PI_2,
+      pitch: 0.0,
+      pressed_keys: HashSet::new(),
+      dragging: false,
+      last_cursor: None,
+    }
+  }
+
+  fn forward(&self) -> Vec3 {
+    Vec3::new(
+      self.yaw.cos() * self.pitch.cos(),
+      self.pitch.sin(),
+      self.yaw.sin() * self.pitch.cos(),
+    )
+    .normalize()
+  }
+
+  fn camera(&self) -> render::Camera {
+    render::Camera::new(self.position, self.forward(), Vec3::Y, 40.0)
+  }
+
+  fn handle_key(&mut self, key: KeyCode, state: ElementState) {
+    match state {
+      ElementState::Pressed => {
+        self.pressed_keys.insert(key);
+      }
+      ElementState::Released => {
+        self.pressed_keys.remove(&key);
+      }
+    }
+  }
+
+  fn handle_mouse_button(&mut self, button: MouseButton, state: ElementState) {
+    if button == MouseButton::Left {
+      self.dragging = state == ElementState::Pressed;
+      if !self.dragging {
+        self.last_cursor = None;
+      }
+    }
+  }
+
+  /// Returns `true` if the drag rotated the camera this call.
+  fn handle_cursor_moved(&mut self, x: f64, y: f64) -> bool {
+    if !self.dragging {
+      self.last_cursor = Some((x, y));
+      return false;
+    }
+    let rotated = if let Some((last_x, last_y)) = self.last_cursor {
+      let dx = (x - last_x) as f32;
+      let dy = (y - last_y) as f32;
+      self.yaw += dx * LOOK_SPEED;
+      self.pitch = (self.pitch - dy * LOOK_SPEED).clamp(-1.5, 1.5);
+      dx != 0.0 || dy != 0.0
+    } else {
+      false
+    };
+    self.last_cursor = Some((x, y));
+    rotated
+  }
+
+  /// Returns `true` if WASD movement changed the camera position this call.
+  fn apply_movement(&mut self) -> bool {
+    let forward = self.forward();
+    let right = forward.cross(Vec3::Y).normalize();
+    let mut moved = false;
+    let mut step = |direction: Vec3| {
+      self.position += direction * MOVE_SPEED;
+      moved = true;
+    };
+    if self.pressed_keys.contains(&KeyCode::KeyW) {
+      step(forward);
+    }
+    if self.pressed_keys.contains(&KeyCode::KeyS) {
+      step(-forward);
+    }
+    if self.pressed_keys.contains(&KeyCode::KeyD) {
+      step(right);
+    }
+    if self.pressed_keys.contains(&KeyCode::KeyA) {
+      step(-right);
+    }
+    moved
+  }
+}
+
+/// `--output <file> --samples N` skips window/surface creation entirely and
+/// renders `N` accumulated samples straight to a PNG.
+struct Cli {
+  output: Option<std::path::PathBuf>,
+  samples: u32,
+}
+
+fn parse_cli() -> Cli {
+  let mut output = None;
+  let mut samples = 32;
+  let mut args = std::env::args().skip(1);
+  while let Some(arg) = args.next() {
+    match arg.as_str() {
+      "--output" => output = args.next().map(std::path::PathBuf::from),
+      "--samples" => {
+        if let Some(value) = args.next() {
+          samples = value.parse().unwrap_or(samples);
+        }
+      }
+      _ => (),
+    }
+  }
+  Cli { output, samples }
+}
 
 #[pollster::main]
 async fn main() -> Result<()> {
+  let cli = parse_cli();
+  if let Some(output_path) = cli.output {
+    return render_headless(&output_path, cli.samples).await;
+  }
+
   let event_loop = EventLoop::new()?;
   let window_size = winit::dpi::LogicalSize::new(WIDTH, HEIGHT);
   let window = WindowBuilder::new()
     .with_inner_size(window_size)
-    .with_resizable(false)
+    .with_resizable(true)
     .with_title("GPU Path Tracer")
     .build(&event_loop)?;
-  let (device, queue, surface, format) = connect_to_gpu(&window).await?;
+  let (device, queue, surface, mut surface_config, format) = connect_to_gpu(&window).await?;
+  let gpu_device = device.clone();
   let physical_size = window.inner_size();
-  let renderer = render::PathTracer::new(
+  let mut renderer = render::PathTracer::new(
     device,
     queue,
     physical_size.width,
@@ -27,12 +165,62 @@ async fn main() -> Result<()> {
     format,
   );
 
+  let mut controller = CameraController::new(Vec3::new(0.0, 0.0, 1.0));
+  renderer.update_camera(controller.camera());
+  let mut tonemap_mode = render::TonemapMode::default();
+  let mut exposure = 0.0f32;
+
   event_loop.run(|event, control_handle| {
     control_handle.set_control_flow(ControlFlow::Poll);
     if let Event::WindowEvent { event, .. } = event {
       match event {
         WindowEvent::CloseRequested => control_handle.exit(),
+        WindowEvent::KeyboardInput { event, .. } => {
+          if let PhysicalKey::Code(code) = event.physical_key {
+            controller.handle_key(code, event.state);
+            // Single-fire controls, keyed off the press edge so holding
+            // the key down doesn't cycle/adjust every redraw.
+            if event.state == ElementState::Pressed && !event.repeat {
+              match code {
+                KeyCode::KeyT => {
+                  tonemap_mode = tonemap_mode.next();
+                  renderer.set_tonemap(tonemap_mode);
+                }
+                KeyCode::BracketRight => {
+                  exposure += 0.25;
+                  renderer.set_exposure(exposure);
+                }
+                KeyCode::BracketLeft => {
+                  exposure -= 0.25;
+                  renderer.set_exposure(exposure);
+                }
+                _ => (),
+              }
+            }
+          }
+        }
+        WindowEvent::MouseInput { state, button, .. } => {
+          controller.handle_mouse_button(button, state);
+        }
+        WindowEvent::CursorMoved { position, .. } => {
+          if controller.handle_cursor_moved(position.x, position.y) {
+            renderer.update_camera(controller.camera());
+          }
+        }
+        WindowEvent::Resized(new_size) => {
+          // Minimizing fires a 0x0 resize; reconfiguring the surface to
+          // that size is invalid, so just skip it.
+          if new_size.width > 0 && new_size.height > 0 {
+            surface_config.width = new_size.width;
+            surface_config.height = new_size.height;
+            surface.configure(&gpu_device, &surface_config);
+            renderer.resize(new_size.width, new_size.height);
+          }
+        }
         WindowEvent::RedrawRequested => {
+          if controller.apply_movement() {
+            renderer.update_camera(controller.camera());
+          }
           let frame: wgpu::SurfaceTexture = surface
             .get_current_texture()
             .expect("Failed to get current texture");
@@ -51,12 +239,48 @@ async fn main() -> Result<()> {
   Ok(())
 }
 
+async fn render_headless(output_path: &std::path::Path, samples: u32) -> Result<()> {
+  let (device, queue) = connect_to_gpu_headless().await?;
+  let mut renderer =
+    render::PathTracer::new(device, queue, WIDTH, HEIGHT, wgpu::TextureFormat::Rgba8Unorm);
+  renderer.update_camera(render::Camera::new(
+    Vec3::new(0.0, 0.0, 1.0),
+    Vec3::NEG_Z,
+    Vec3::Y,
+    40.0,
+  ));
+
+  let image = renderer.render_to_image(samples);
+  image
+    .save(output_path)
+    .with_context(|| format!("Failed to save output image to {}", output_path.display()))?;
+  Ok(())
+}
+
+async fn connect_to_gpu_headless() -> Result<(wgpu::Device, wgpu::Queue)> {
+  let instance = wgpu::Instance::default();
+  let adapter = instance
+    .request_adapter(&wgpu::RequestAdapterOptions {
+      power_preference: wgpu::PowerPreference::HighPerformance,
+      force_fallback_adapter: false,
+      compatible_surface: None,
+    })
+    .await
+    .context("Failed to find a compatible adapter")?;
+  let (device, queue) = adapter
+    .request_device(&wgpu::DeviceDescriptor::default())
+    .await
+    .context("Failed to connect to the GPU")?;
+  Ok((device, queue))
+}
+
 async fn connect_to_gpu(
   window: &'_ Window,
 ) -> Result<(
   wgpu::Device,
   wgpu::Queue,
   wgpu::Surface<'_>,
+  wgpu::SurfaceConfiguration,
   wgpu::TextureFormat,
 )> {
   use wgpu::TextureFormat::{Bgra8Unorm, Rgba8Unorm};
@@ -94,5 +318,5 @@ async fn connect_to_gpu(
     view_formats: vec![],
   };
   surface.configure(&device, &config);
-  Ok((device, queue, surface, format))
+  Ok((device, queue, surface, config, format))
 }
@@ -1,20 +1,111 @@
-use bytemuck::{Pod, Zeroable};
+use encase::ShaderType;
+use glam::Vec3;
+use wgpu::util::DeviceExt;
 use wgpu::{Device, Queue};
 
-#[derive(Clone, Copy, Pod, Zeroable)]
-#[repr(C)]
+use crate::scene::{storage_bytes, Scene};
+
+#[derive(Clone, Copy, ShaderType)]
 struct Uniforms {
   width: u32,
   height: u32,
+  frame_index: u32,
+  sample_count: u32,
+  origin: Vec3,
+  lower_left_corner: Vec3,
+  horizontal: Vec3,
+  vertical: Vec3,
+  tonemap_mode: u32,
+  exposure: f32,
+}
+
+/// Selects the display-pass tonemap curve applied to the HDR accumulation
+/// buffer before the linear-to-sRGB encode.
+#[derive(Clone, Copy, Default)]
+pub enum TonemapMode {
+  #[default]
+  Reinhard = 0,
+  AcesFilmic = 1,
+  Clamp = 2,
+}
+
+impl TonemapMode {
+  /// Cycles to the next mode, wrapping back to `Reinhard` — drives a
+  /// single-key toggle rather than needing a full UI.
+  pub fn next(self) -> TonemapMode {
+    match self {
+      TonemapMode::Reinhard => TonemapMode::AcesFilmic,
+      TonemapMode::AcesFilmic => TonemapMode::Clamp,
+      TonemapMode::Clamp => TonemapMode::Reinhard,
+    }
+  }
+}
+
+/// A pinhole camera that produces the ray-generation basis vectors
+/// baked into `Uniforms`: `lower_left_corner + u*horizontal + v*vertical`
+/// (minus `origin`) gives the primary ray direction for pixel `(u, v)`.
+#[derive(Clone, Copy)]
+pub struct Camera {
+  pub position: Vec3,
+  pub forward: Vec3,
+  pub up: Vec3,
+  pub vfov_degrees: f32,
+}
+
+impl Camera {
+  pub fn new(position: Vec3, forward: Vec3, up: Vec3, vfov_degrees: f32) -> Camera {
+    Camera {
+      position,
+      forward: forward.normalize(),
+      up,
+      vfov_degrees,
+    }
+  }
+
+  fn ray_basis(&self, aspect_ratio: f32) -> (Vec3, Vec3, Vec3) {
+    let theta = self.vfov_degrees.to_radians();
+    let viewport_height = 2.0 * (theta / 2.0).tan();
+    let viewport_width = aspect_ratio * viewport_height;
+
+    let w = -self.forward;
+    let u = self.up.cross(w).normalize();
+    let v = w.cross(u);
+
+    let horizontal = viewport_width * u;
+    let vertical = viewport_height * v;
+    let lower_left_corner = self.position - horizontal / 2.0 - vertical / 2.0 - w;
+    (lower_left_corner, horizontal, vertical)
+  }
 }
 
 pub struct PathTracer {
   device: Device,
-  display_bind_group: wgpu::BindGroup,
-  display_pipeline: wgpu::RenderPipeline,
   queue: Queue,
-  uniform_buffer: wgpu::Buffer,
+  width: u32,
+  height: u32,
+  camera: Camera,
+
   uniforms: Uniforms,
+  uniform_buffer: wgpu::Buffer,
+
+  /// Ping-ponged accumulation buffers: each frame reads the previous result
+  /// out of one and writes the new blended result into the other, so the
+  /// compute shader never needs read-write access to a storage texture.
+  /// `accum_index` names the texture holding the most recently written
+  /// (i.e. current/latest) result.
+  accum_textures: [wgpu::Texture; 2],
+  accum_index: usize,
+  accum_sampler: wgpu::Sampler,
+  compute_layout: wgpu::BindGroupLayout,
+  compute_bind_groups: [wgpu::BindGroup; 2],
+  compute_pipeline: wgpu::ComputePipeline,
+
+  scene_layout: wgpu::BindGroupLayout,
+  scene_bind_group: wgpu::BindGroup,
+
+  display_layout: wgpu::BindGroupLayout,
+  display_bind_groups: [wgpu::BindGroup; 2],
+  display_pipeline: wgpu::RenderPipeline,
 }
 
 impl PathTracer {
@@ -29,52 +120,156 @@ impl PathTracer {
       panic!("Aborting due to an error: {e}")
     }));
     let shader_module = compile_shader_module(&device);
-    let (display_pipeline, display_layout) =
-      create_display_pipeline(&device, &shader_module, format);
-
-    let uniforms = Uniforms { width, height };
 
+    let camera = Camera::new(Vec3::new(0.0, 0.0, 1.0), Vec3::NEG_Z, Vec3::Y, 40.0);
+    let (lower_left_corner, horizontal, vertical) =
+      camera.ray_basis(width as f32 / height as f32);
+    let uniforms = Uniforms {
+      width,
+      height,
+      frame_index: 0,
+      sample_count: 0,
+      origin: camera.position,
+      lower_left_corner,
+      horizontal,
+      vertical,
+      tonemap_mode: TonemapMode::default() as u32,
+      exposure: 0.0,
+    };
+    let uniform_bytes = encode_uniforms(&uniforms);
     let uniform_buffer = device.create_buffer(&wgpu::BufferDescriptor {
       label: Some("uniforms"),
-      size: std::mem::size_of::<Uniforms>() as u64,
+      size: uniform_bytes.len() as u64,
       usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
-      mapped_at_creation: true,
+      mapped_at_creation: false,
     });
-    uniform_buffer
-      .slice(..)
-      .get_mapped_range_mut()
-      .copy_from_slice(bytemuck::bytes_of(&uniforms));
-    uniform_buffer.unmap();
-
-    let display_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
-      label: None,
-      layout: &display_layout,
-      entries: &[wgpu::BindGroupEntry {
-        binding: 0,
-        resource: wgpu::BindingResource::Buffer(wgpu::BufferBinding {
-          buffer: &uniform_buffer,
-          offset: 0,
-          size: None,
-        }),
-      }],
+    queue.write_buffer(&uniform_buffer, 0, &uniform_bytes);
+
+    let accum_textures = create_accumulation_textures(&device, width, height);
+    let accum_sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+      label: Some("accumulation sampler"),
+      mag_filter: wgpu::FilterMode::Nearest,
+      min_filter: wgpu::FilterMode::Nearest,
+      ..Default::default()
     });
 
+    let (display_pipeline, display_layout) =
+      create_display_pipeline(&device, &shader_module, format);
+    let display_bind_groups = create_display_bind_groups(
+      &device,
+      &display_layout,
+      &uniform_buffer,
+      &accum_textures,
+      &accum_sampler,
+    );
+
+    let scene_layout = create_scene_bind_group_layout(&device);
+    let (compute_pipeline, compute_layout) =
+      create_compute_pipeline(&device, &shader_module, &display_layout, &scene_layout);
+    let compute_bind_groups =
+      create_compute_bind_groups(&device, &compute_layout, &accum_textures);
+    let scene_bind_group = create_scene_bind_group(&device, &scene_layout, &Scene::new());
+
     PathTracer {
       device,
       queue,
+      width,
+      height,
+      camera,
       uniforms,
       uniform_buffer,
+      accum_textures,
+      accum_index: 0,
+      accum_sampler,
+      compute_layout,
+      compute_bind_groups,
+      compute_pipeline,
+      scene_layout,
+      scene_bind_group,
+      display_layout,
+      display_bind_groups,
       display_pipeline,
-      display_bind_group,
     }
   }
 
-  pub fn render_frame(&self, target: &wgpu::TextureView) {
+  pub fn render_frame(&mut self, target: &wgpu::TextureView) {
     let mut encoder = self
       .device
       .create_command_encoder(&wgpu::CommandEncoderDescriptor {
         label: Some("render frame"),
       });
+    self.encode_radiance_pass(&mut encoder);
+    self.encode_display_pass(&mut encoder, target);
+    self.queue.submit(Some(encoder.finish()));
+  }
+
+  /// Renders `samples` accumulated frames offscreen and reads the result
+  /// back into a CPU-side image, for headless/deterministic output.
+  pub fn render_to_image(&mut self, samples: u32) -> image::RgbaImage {
+    self.reset_accumulation();
+    let output_texture = self.device.create_texture(&wgpu::TextureDescriptor {
+      label: Some("offscreen target"),
+      size: wgpu::Extent3d {
+        width: self.width,
+        height: self.height,
+        depth_or_array_layers: 1,
+      },
+      mip_level_count: 1,
+      sample_count: 1,
+      dimension: wgpu::TextureDimension::D2,
+      format: wgpu::TextureFormat::Rgba8Unorm,
+      usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+      view_formats: &[],
+    });
+    let output_view = output_texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+    for _ in 0..samples {
+      let mut encoder = self
+        .device
+        .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+          label: Some("accumulate sample"),
+        });
+      self.encode_radiance_pass(&mut encoder);
+      self.queue.submit(Some(encoder.finish()));
+    }
+
+    let mut encoder = self
+      .device
+      .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+        label: Some("offscreen display pass"),
+      });
+    self.encode_display_pass(&mut encoder, &output_view);
+    self.queue.submit(Some(encoder.finish()));
+
+    read_texture_to_image(&self.device, &self.queue, &output_texture, self.width, self.height)
+  }
+
+  fn encode_radiance_pass(&mut self, encoder: &mut wgpu::CommandEncoder) {
+    self.uniforms.frame_index += 1;
+    self.uniforms.sample_count += 1;
+    self.write_uniforms();
+
+    // `compute_bind_groups[read_index]` reads the current texture and
+    // writes the other one; flip `accum_index` to the freshly written
+    // texture so the next pass (this frame's display, and next frame's
+    // radiance pass) picks it up.
+    let read_index = self.accum_index;
+    let mut compute_pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+      label: Some("radiance pass"),
+      timestamp_writes: None,
+    });
+    compute_pass.set_pipeline(&self.compute_pipeline);
+    compute_pass.set_bind_group(0, &self.display_bind_groups[read_index], &[]);
+    compute_pass.set_bind_group(1, &self.compute_bind_groups[read_index], &[]);
+    compute_pass.set_bind_group(2, &self.scene_bind_group, &[]);
+    let workgroups_x = self.width.div_ceil(8);
+    let workgroups_y = self.height.div_ceil(8);
+    compute_pass.dispatch_workgroups(workgroups_x, workgroups_y, 1);
+    drop(compute_pass);
+    self.accum_index = 1 - read_index;
+  }
+
+  fn encode_display_pass(&self, encoder: &mut wgpu::CommandEncoder, target: &wgpu::TextureView) {
     let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
       label: Some("display pass"),
       color_attachments: &[Some(wgpu::RenderPassColorAttachment {
@@ -89,14 +284,259 @@ impl PathTracer {
       ..Default::default()
     });
     render_pass.set_pipeline(&self.display_pipeline);
-    render_pass.set_bind_group(0, &self.display_bind_group, &[]);
+    render_pass.set_bind_group(0, &self.display_bind_groups[self.accum_index], &[]);
     render_pass.draw(0..6, 0..1);
-    drop(render_pass);
-    let command_buffer = encoder.finish();
-    self.queue.submit(Some(command_buffer));
+  }
+
+  /// Restarts progressive accumulation, e.g. after the camera or scene changes.
+  fn reset_accumulation(&mut self) {
+    self.uniforms.frame_index = 0;
+  }
+
+  pub fn update_camera(&mut self, camera: Camera) {
+    let aspect_ratio = self.width as f32 / self.height as f32;
+    let (lower_left_corner, horizontal, vertical) = camera.ray_basis(aspect_ratio);
+    self.camera = camera;
+    self.uniforms.origin = camera.position;
+    self.uniforms.lower_left_corner = lower_left_corner;
+    self.uniforms.horizontal = horizontal;
+    self.uniforms.vertical = vertical;
+    self.reset_accumulation();
+    self.write_uniforms();
+  }
+
+  /// Reallocates the accumulation textures for a new window size and
+  /// restarts accumulation. Does not touch the presentation surface — the
+  /// caller owns that and must reconfigure it alongside this call.
+  pub fn resize(&mut self, width: u32, height: u32) {
+    self.width = width;
+    self.height = height;
+    self.uniforms.width = width;
+    self.uniforms.height = height;
+
+    let accum_textures = create_accumulation_textures(&self.device, width, height);
+    self.display_bind_groups = create_display_bind_groups(
+      &self.device,
+      &self.display_layout,
+      &self.uniform_buffer,
+      &accum_textures,
+      &self.accum_sampler,
+    );
+    self.compute_bind_groups =
+      create_compute_bind_groups(&self.device, &self.compute_layout, &accum_textures);
+    self.accum_textures = accum_textures;
+    self.accum_index = 0;
+
+    let aspect_ratio = width as f32 / height as f32;
+    let (lower_left_corner, horizontal, vertical) = self.camera.ray_basis(aspect_ratio);
+    self.uniforms.lower_left_corner = lower_left_corner;
+    self.uniforms.horizontal = horizontal;
+    self.uniforms.vertical = vertical;
+
+    self.reset_accumulation();
+    self.write_uniforms();
+  }
+
+  /// Uploads `scene` as read-only storage buffers and restarts accumulation.
+  pub fn load_scene(&mut self, scene: &Scene) {
+    self.scene_bind_group = create_scene_bind_group(&self.device, &self.scene_layout, scene);
+    self.reset_accumulation();
+  }
+
+  pub fn set_tonemap(&mut self, mode: TonemapMode) {
+    self.uniforms.tonemap_mode = mode as u32;
+    self.write_uniforms();
+  }
+
+  pub fn set_exposure(&mut self, exposure: f32) {
+    self.uniforms.exposure = exposure;
+    self.write_uniforms();
+  }
+
+  /// Serializes `self.uniforms` with `encase`'s std140 layout and uploads it.
+  fn write_uniforms(&self) {
+    let bytes = encode_uniforms(&self.uniforms);
+    self.queue.write_buffer(&self.uniform_buffer, 0, &bytes);
   }
 }
 
+/// Copies an `Rgba8Unorm` texture into a mapped readback buffer, honoring
+/// wgpu's 256-byte `bytes_per_row` alignment, and decodes it into an image.
+fn read_texture_to_image(
+  device: &Device,
+  queue: &Queue,
+  texture: &wgpu::Texture,
+  width: u32,
+  height: u32,
+) -> image::RgbaImage {
+  const BYTES_PER_PIXEL: u32 = 4;
+  const ROW_ALIGNMENT: u32 = wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+
+  let unpadded_bytes_per_row = width * BYTES_PER_PIXEL;
+  let padded_bytes_per_row = unpadded_bytes_per_row.div_ceil(ROW_ALIGNMENT) * ROW_ALIGNMENT;
+
+  let readback_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+    label: Some("readback buffer"),
+    size: (padded_bytes_per_row * height) as u64,
+    usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+    mapped_at_creation: false,
+  });
+
+  let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+    label: Some("readback copy"),
+  });
+  encoder.copy_texture_to_buffer(
+    texture.as_image_copy(),
+    wgpu::TexelCopyBufferInfo {
+      buffer: &readback_buffer,
+      layout: wgpu::TexelCopyBufferLayout {
+        offset: 0,
+        bytes_per_row: Some(padded_bytes_per_row),
+        rows_per_image: Some(height),
+      },
+    },
+    wgpu::Extent3d {
+      width,
+      height,
+      depth_or_array_layers: 1,
+    },
+  );
+  queue.submit(Some(encoder.finish()));
+
+  let slice = readback_buffer.slice(..);
+  let (tx, rx) = std::sync::mpsc::channel();
+  slice.map_async(wgpu::MapMode::Read, move |result| {
+    tx.send(result).expect("readback channel should be alive");
+  });
+  device.poll(wgpu::Maintain::Wait).panic_on_timeout();
+  rx.recv()
+    .expect("map_async callback should fire")
+    .expect("failed to map readback buffer");
+
+  let padded = slice.get_mapped_range();
+  let mut pixels = Vec::with_capacity((unpadded_bytes_per_row * height) as usize);
+  for row in padded.chunks_exact(padded_bytes_per_row as usize) {
+    pixels.extend_from_slice(&row[..unpadded_bytes_per_row as usize]);
+  }
+  drop(padded);
+  readback_buffer.unmap();
+
+  image::RgbaImage::from_raw(width, height, pixels)
+    .expect("readback buffer should be exactly width * height * 4 bytes")
+}
+
+fn encode_uniforms(uniforms: &Uniforms) -> Vec<u8> {
+  let mut buffer = encase::UniformBuffer::new(Vec::new());
+  buffer
+    .write(uniforms)
+    .expect("Uniforms layout should always serialize");
+  buffer.into_inner()
+}
+
+/// Creates the two ping-ponged accumulation textures (see `PathTracer::accum_textures`).
+fn create_accumulation_textures(device: &Device, width: u32, height: u32) -> [wgpu::Texture; 2] {
+  let descriptor = wgpu::TextureDescriptor {
+    label: Some("accumulation texture"),
+    size: wgpu::Extent3d {
+      width,
+      height,
+      depth_or_array_layers: 1,
+    },
+    mip_level_count: 1,
+    sample_count: 1,
+    dimension: wgpu::TextureDimension::D2,
+    format: wgpu::TextureFormat::Rgba32Float,
+    usage: wgpu::TextureUsages::STORAGE_BINDING | wgpu::TextureUsages::TEXTURE_BINDING,
+    view_formats: &[],
+  };
+  [
+    device.create_texture(&descriptor),
+    device.create_texture(&descriptor),
+  ]
+}
+
+fn create_display_bind_group(
+  device: &Device,
+  layout: &wgpu::BindGroupLayout,
+  uniform_buffer: &wgpu::Buffer,
+  accum_sample_view: &wgpu::TextureView,
+  accum_sampler: &wgpu::Sampler,
+) -> wgpu::BindGroup {
+  device.create_bind_group(&wgpu::BindGroupDescriptor {
+    label: Some("display bind group"),
+    layout,
+    entries: &[
+      wgpu::BindGroupEntry {
+        binding: 0,
+        resource: wgpu::BindingResource::Buffer(wgpu::BufferBinding {
+          buffer: uniform_buffer,
+          offset: 0,
+          size: None,
+        }),
+      },
+      wgpu::BindGroupEntry {
+        binding: 1,
+        resource: wgpu::BindingResource::TextureView(accum_sample_view),
+      },
+      wgpu::BindGroupEntry {
+        binding: 2,
+        resource: wgpu::BindingResource::Sampler(accum_sampler),
+      },
+    ],
+  })
+}
+
+/// Builds one display bind group per accumulation texture, so the display
+/// pass can sample whichever one `PathTracer::accum_index` currently names.
+fn create_display_bind_groups(
+  device: &Device,
+  layout: &wgpu::BindGroupLayout,
+  uniform_buffer: &wgpu::Buffer,
+  accum_textures: &[wgpu::Texture; 2],
+  accum_sampler: &wgpu::Sampler,
+) -> [wgpu::BindGroup; 2] {
+  let views = [
+    accum_textures[0].create_view(&wgpu::TextureViewDescriptor::default()),
+    accum_textures[1].create_view(&wgpu::TextureViewDescriptor::default()),
+  ];
+  [
+    create_display_bind_group(device, layout, uniform_buffer, &views[0], accum_sampler),
+    create_display_bind_group(device, layout, uniform_buffer, &views[1], accum_sampler),
+  ]
+}
+
+/// Builds one compute bind group per read/write direction of the
+/// ping-pong: index `i` reads `accum_textures[i]` (as a plain sampled
+/// texture) and writes `accum_textures[1 - i]` (as a write-only storage
+/// texture), so the shader never needs read-write storage texture access.
+fn create_compute_bind_groups(
+  device: &Device,
+  layout: &wgpu::BindGroupLayout,
+  accum_textures: &[wgpu::Texture; 2],
+) -> [wgpu::BindGroup; 2] {
+  let views = [
+    accum_textures[0].create_view(&wgpu::TextureViewDescriptor::default()),
+    accum_textures[1].create_view(&wgpu::TextureViewDescriptor::default()),
+  ];
+  let make = |read: usize, write: usize| {
+    device.create_bind_group(&wgpu::BindGroupDescriptor {
+      label: Some("compute bind group"),
+      layout,
+      entries: &[
+        wgpu::BindGroupEntry {
+          binding: 0,
+          resource: wgpu::BindingResource::TextureView(&views[read]),
+        },
+        wgpu::BindGroupEntry {
+          binding: 1,
+          resource: wgpu::BindingResource::TextureView(&views[write]),
+        },
+      ],
+    })
+  };
+  [make(0, 1), make(1, 0)]
+}
+
 fn compile_shader_module(device: &Device) -> wgpu::ShaderModule {
   use std::borrow::Cow;
   let code = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/src/shaders.wgsl"));
@@ -112,17 +552,35 @@ fn create_display_pipeline(
   format: wgpu::TextureFormat,
 ) -> (wgpu::RenderPipeline, wgpu::BindGroupLayout) {
   let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
-    label: None,
-    entries: &[wgpu::BindGroupLayoutEntry {
-      binding: 0,
-      visibility: wgpu::ShaderStages::FRAGMENT,
-      ty: wgpu::BindingType::Buffer {
-        ty: wgpu::BufferBindingType::Uniform,
-        has_dynamic_offset: false,
-        min_binding_size: None,
+    label: Some("display bind group layout"),
+    entries: &[
+      wgpu::BindGroupLayoutEntry {
+        binding: 0,
+        visibility: wgpu::ShaderStages::FRAGMENT | wgpu::ShaderStages::COMPUTE,
+        ty: wgpu::BindingType::Buffer {
+          ty: wgpu::BufferBindingType::Uniform,
+          has_dynamic_offset: false,
+          min_binding_size: None,
+        },
+        count: None,
       },
-      count: None,
-    }],
+      wgpu::BindGroupLayoutEntry {
+        binding: 1,
+        visibility: wgpu::ShaderStages::FRAGMENT,
+        ty: wgpu::BindingType::Texture {
+          sample_type: wgpu::TextureSampleType::Float { filterable: false },
+          view_dimension: wgpu::TextureViewDimension::D2,
+          multisampled: false,
+        },
+        count: None,
+      },
+      wgpu::BindGroupLayoutEntry {
+        binding: 2,
+        visibility: wgpu::ShaderStages::FRAGMENT,
+        ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::NonFiltering),
+        count: None,
+      },
+    ],
   });
 
   let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
@@ -162,3 +620,147 @@ fn create_display_pipeline(
   });
   (pipeline, bind_group_layout)
 }
+
+fn create_compute_pipeline(
+  device: &wgpu::Device,
+  shader_module: &wgpu::ShaderModule,
+  uniform_layout: &wgpu::BindGroupLayout,
+  scene_layout: &wgpu::BindGroupLayout,
+) -> (wgpu::ComputePipeline, wgpu::BindGroupLayout) {
+  // Both bindings stay within core WebGPU: a plain sampled texture for the
+  // previous accumulation result, and write-only storage for the new one.
+  // `StorageTextureAccess::ReadWrite` on `rgba32float` would require
+  // `Features::TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES`, which isn't
+  // requested anywhere `request_device` is called — see the ping-pong
+  // textures in `PathTracer`.
+  let storage_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+    label: Some("radiance storage layout"),
+    entries: &[
+      wgpu::BindGroupLayoutEntry {
+        binding: 0,
+        visibility: wgpu::ShaderStages::COMPUTE,
+        ty: wgpu::BindingType::Texture {
+          sample_type: wgpu::TextureSampleType::Float { filterable: false },
+          view_dimension: wgpu::TextureViewDimension::D2,
+          multisampled: false,
+        },
+        count: None,
+      },
+      wgpu::BindGroupLayoutEntry {
+        binding: 1,
+        visibility: wgpu::ShaderStages::COMPUTE,
+        ty: wgpu::BindingType::StorageTexture {
+          access: wgpu::StorageTextureAccess::WriteOnly,
+          format: wgpu::TextureFormat::Rgba32Float,
+          view_dimension: wgpu::TextureViewDimension::D2,
+        },
+        count: None,
+      },
+    ],
+  });
+
+  let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+    label: Some("radiance"),
+    layout: Some(
+      &device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+        bind_group_layouts: &[uniform_layout, &storage_layout, scene_layout],
+        ..Default::default()
+      }),
+    ),
+    module: shader_module,
+    entry_point: Some("radiance_cs"),
+    compilation_options: wgpu::PipelineCompilationOptions::default(),
+    cache: None,
+  });
+  (pipeline, storage_layout)
+}
+
+fn storage_layout_entry(binding: u32) -> wgpu::BindGroupLayoutEntry {
+  wgpu::BindGroupLayoutEntry {
+    binding,
+    visibility: wgpu::ShaderStages::COMPUTE,
+    ty: wgpu::BindingType::Buffer {
+      ty: wgpu::BufferBindingType::Storage { read_only: true },
+      has_dynamic_offset: false,
+      min_binding_size: None,
+    },
+    count: None,
+  }
+}
+
+fn create_scene_bind_group_layout(device: &Device) -> wgpu::BindGroupLayout {
+  device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+    label: Some("scene bind group layout"),
+    entries: &[
+      storage_layout_entry(0),
+      storage_layout_entry(1),
+      storage_layout_entry(2),
+    ],
+  })
+}
+
+fn create_scene_bind_group(
+  device: &Device,
+  layout: &wgpu::BindGroupLayout,
+  scene: &Scene,
+) -> wgpu::BindGroup {
+  let spheres_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+    label: Some("scene spheres"),
+    contents: &storage_bytes(&scene.spheres),
+    usage: wgpu::BufferUsages::STORAGE,
+  });
+  let materials_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+    label: Some("scene materials"),
+    contents: &storage_bytes(&scene.materials),
+    usage: wgpu::BufferUsages::STORAGE,
+  });
+  let triangles_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+    label: Some("scene triangles"),
+    contents: &storage_bytes(&scene.triangles),
+    usage: wgpu::BufferUsages::STORAGE,
+  });
+
+  device.create_bind_group(&wgpu::BindGroupDescriptor {
+    label: Some("scene bind group"),
+    layout,
+    entries: &[
+      wgpu::BindGroupEntry {
+        binding: 0,
+        resource: spheres_buffer.as_entire_binding(),
+      },
+      wgpu::BindGroupEntry {
+        binding: 1,
+        resource: materials_buffer.as_entire_binding(),
+      },
+      wgpu::BindGroupEntry {
+        binding: 2,
+        resource: triangles_buffer.as_entire_binding(),
+      },
+    ],
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn ray_basis_is_centered_on_the_forward_direction() {
+    let camera = Camera::new(Vec3::ZERO, Vec3::NEG_Z, Vec3::Y, 90.0);
+    let (lower_left_corner, horizontal, vertical) = camera.ray_basis(1.0);
+    let viewport_center = lower_left_corner + horizontal / 2.0 + vertical / 2.0;
+    assert!((viewport_center - (camera.position + camera.forward)).length() < 1e-4);
+  }
+
+  #[test]
+  fn tonemap_mode_cycles_through_all_variants_and_wraps() {
+    let reinhard = TonemapMode::default();
+    let aces = reinhard.next();
+    let clamp = aces.next();
+    let wrapped = clamp.next();
+    assert_eq!(reinhard as u32, 0);
+    assert_eq!(aces as u32, 1);
+    assert_eq!(clamp as u32, 2);
+    assert_eq!(wrapped as u32, 0);
+  }
+}
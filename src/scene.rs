@@ -0,0 +1,162 @@
+use std::path::Path;
+
+use bytemuck::{Pod, Zeroable};
+use glam::Vec3;
+
+pub const MATERIAL_LAMBERTIAN: u32 = 0;
+pub const MATERIAL_METAL: u32 = 1;
+pub const MATERIAL_DIELECTRIC: u32 = 2;
+
+#[derive(Clone, Copy, Pod, Zeroable)]
+#[repr(C)]
+pub struct Sphere {
+  pub center: Vec3,
+  pub radius: f32,
+  pub material_id: u32,
+  _padding: [u32; 3],
+}
+
+#[derive(Clone, Copy, Pod, Zeroable)]
+#[repr(C)]
+pub struct Material {
+  pub albedo: Vec3,
+  pub roughness: f32,
+  pub emission: Vec3,
+  pub material_type: u32,
+}
+
+impl Material {
+  pub fn lambertian(albedo: Vec3) -> Material {
+    Material {
+      albedo,
+      roughness: 1.0,
+      emission: Vec3::ZERO,
+      material_type: MATERIAL_LAMBERTIAN,
+    }
+  }
+
+  pub fn metal(albedo: Vec3, roughness: f32) -> Material {
+    Material {
+      albedo,
+      roughness,
+      emission: Vec3::ZERO,
+      material_type: MATERIAL_METAL,
+    }
+  }
+
+  /// `ior` is the material's index of refraction (glass is ~1.5); it's
+  /// stored in the `roughness` field since dielectrics have no use for
+  /// microfacet roughness.
+  pub fn dielectric(ior: f32) -> Material {
+    Material {
+      albedo: Vec3::ONE,
+      roughness: ior,
+      emission: Vec3::ZERO,
+      material_type: MATERIAL_DIELECTRIC,
+    }
+  }
+}
+
+#[derive(Clone, Copy, Pod, Zeroable)]
+#[repr(C)]
+pub struct Triangle {
+  pub v0: Vec3,
+  _pad0: f32,
+  pub v1: Vec3,
+  _pad1: f32,
+  pub v2: Vec3,
+  pub material_id: u32,
+}
+
+/// CPU-side scene description. `PathTracer::load_scene` uploads this as a
+/// set of read-only storage buffers the compute tracer indexes directly.
+#[derive(Default)]
+pub struct Scene {
+  pub spheres: Vec<Sphere>,
+  pub materials: Vec<Material>,
+  pub triangles: Vec<Triangle>,
+}
+
+impl Scene {
+  pub fn new() -> Scene {
+    Scene::default()
+  }
+
+  /// Adds a sphere with its own material, returning the material's id.
+  pub fn add_sphere(&mut self, center: Vec3, radius: f32, material: Material) -> u32 {
+    let material_id = self.materials.len() as u32;
+    self.materials.push(material);
+    self.spheres.push(Sphere {
+      center,
+      radius,
+      material_id,
+      _padding: [0; 3],
+    });
+    material_id
+  }
+
+  /// Parses an `.obj` file and appends its triangles, all assigned to
+  /// `material_id`. Only positions are used; the tracer has no use for
+  /// normals or UVs yet.
+  pub fn load_obj(&mut self, path: &Path, material_id: u32) -> anyhow::Result<()> {
+    let (models, _materials) = tobj::load_obj(
+      path,
+      &tobj::LoadOptions {
+        triangulate: true,
+        ..Default::default()
+      },
+    )?;
+
+    for model in models {
+      let positions = &model.mesh.positions;
+      let vertex = |index: u32| {
+        let base = index as usize * 3;
+        Vec3::new(positions[base], positions[base + 1], positions[base + 2])
+      };
+      for face in model.mesh.indices.chunks_exact(3) {
+        self.triangles.push(Triangle {
+          v0: vertex(face[0]),
+          _pad0: 0.0,
+          v1: vertex(face[1]),
+          _pad1: 0.0,
+          v2: vertex(face[2]),
+          material_id,
+        });
+      }
+    }
+    Ok(())
+  }
+}
+
+/// Packs a slice into storage-buffer bytes, padding empty scenes with a
+/// single zeroed element so the backing `wgpu::Buffer` is never zero-sized.
+pub(crate) fn storage_bytes<T: Pod>(items: &[T]) -> Vec<u8> {
+  if items.is_empty() {
+    vec![0u8; std::mem::size_of::<T>()]
+  } else {
+    bytemuck::cast_slice(items).to_vec()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn add_sphere_assigns_sequential_material_ids() {
+    let mut scene = Scene::new();
+    let first = scene.add_sphere(Vec3::ZERO, 1.0, Material::lambertian(Vec3::ONE));
+    let second = scene.add_sphere(Vec3::ONE, 1.0, Material::metal(Vec3::ONE, 0.2));
+
+    assert_eq!(first, 0);
+    assert_eq!(second, 1);
+    assert_eq!(scene.spheres.len(), 2);
+    assert_eq!(scene.materials.len(), 2);
+  }
+
+  #[test]
+  fn storage_bytes_pads_empty_slices() {
+    let bytes = storage_bytes::<Sphere>(&[]);
+    assert_eq!(bytes.len(), std::mem::size_of::<Sphere>());
+  }
+}